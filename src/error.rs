@@ -1,9 +1,19 @@
 // Copyright (c) 2024 êµ¬FS, all rights reserved. Subject to the MIT licence in `licence.md`.
+#[cfg(feature = "config_file")]
+use crate::IMPORT_RECURSION_LIMIT;
 
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error
 {
+    #[cfg(feature = "config_file")]
+    #[error("Loading config failed. Ambiguous config sources: both \"{first}\" and \"{second}\" exist, consolidate them into one file.")]
+    AmbiguousSource {first: String, second: String},
+
+    #[cfg(feature = "config_dir")]
+    #[error("Loading config failed. Could not determine the per-user config directory for \"{app_name}\".")]
+    ConfigDirNotFound {app_name: String},
+
     #[error(transparent)]
     CreateDefaultFile(#[from] CreateDefaultFileError), // loading config failed, creating default file failed
 
@@ -12,12 +22,32 @@ pub enum Error
 
     #[error("Loading config failed with: {0}")]
     Figment(#[from] figment::Error), // loading config failed, nothing else could be done
+
+    #[cfg(feature = "config_file")]
+    #[error("Loading config failed. Import cycle detected at \"{filepath}\", it was already imported earlier in this import chain.")]
+    ImportCycle {filepath: String},
+
+    #[cfg(feature = "config_file")]
+    #[error("Loading config failed. Import recursion limit of {IMPORT_RECURSION_LIMIT} exceeded at \"{filepath}\".")]
+    ImportRecursionLimitExceeded {filepath: String},
+
+    #[cfg(feature = "config_file")]
+    #[error("Loading config failed. Resolving import \"{filepath}\" failed with: {source}")]
+    MissingImport {filepath: String, source: std::io::Error},
+
+    #[cfg(feature = "config_file")]
+    #[error("Loading config failed. Import \"{filepath}\" has an unsupported or disabled file format.")]
+    UnsupportedImportFormat {filepath: String},
 }
 
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateDefaultFileError
 {
+    #[cfg(feature = "ron_file")]
+    #[error("Loading config failed. Serialising default config to RON failed with: {0}")]
+    Ron(#[from] ron::Error),
+
     #[cfg(feature = "json_file")]
     #[error("Loading config failed. Serialising default config to JSON failed with: {0}")]
     SerdeJson(#[from] serde_json::Error),
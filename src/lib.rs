@@ -7,6 +7,11 @@ use figment::providers::Format;
 use std::io::Write;
 
 
+/// maximum depth of nested config file imports before `load_config` gives up and returns `Error::ImportRecursionLimitExceeded`, guards against accidentally unbounded import chains
+#[cfg(feature = "config_file")]
+const IMPORT_RECURSION_LIMIT: usize = 5;
+
+
 /// # Summary
 /// Loads config from `sources`, preferring earlier sources. If `config_file_default` is `Some`, a setting is unset, and the specified filepath does not exist yet, offers to create a default config file there. Returns loaded config of type `T` or an error.
 ///
@@ -14,15 +19,134 @@ use std::io::Write;
 /// - `T`: type of config to load, tries to populate its fields from sources
 /// - `sources`: sources to load config from, prefers earlier sources
 /// - `config_file_default`: default config file to create if a setting is unset, optional
+/// - `imports_key`: key under which a `Source::File`'s own content may list other config files to import, defaults to `"imports"` if `None`, see "Imports" below
 ///
 /// # Returns
 /// - successfully loaded config of type `T` or an error
 ///
+/// # Merge Semantics
+/// Each `Source` carries its own `merge` flag. If `merge` is `false` (the default shown in the examples below), the source is joined: it only fills in settings not yet set by an earlier source in `sources`, so the first source in the list to define a setting wins. If `merge` is `true`, the source is merged: it overrides any setting already set by an earlier source, so the last merged source to define a setting wins, regardless of sources after it that are only joined. This lets a later source, e.g. environment variables, override a setting already provided by an earlier one, e.g. a config file, by giving that later source `merge: true`.
+///
+/// # Overrides
+/// `Source::Overrides` carries runtime key/value pairs, e.g. parsed from `--set key=value` CLI flags, and always merges, beating every other source regardless of where it sits in `sources`. A dotted key (`"section.field"`) targets a nested setting.
+///
+/// ```
+/// // an override beats both a file and an env var, for both a flat and a nested, dotted key
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Section
+/// {
+///     pub field: i32,
+/// }
+///
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+///     pub section: Section,
+/// }
+///
+/// std::fs::create_dir_all("./test/overrides").expect("Creating \"./test/overrides\" failed.");
+/// std::fs::write("./test/overrides/config.toml", "setting1 = false\n[section]\nfield = 1").expect("Writing config.toml failed.");
+/// std::env::set_var("SETTING1", "true");
+///
+/// let config: Config = load_config::load_config
+/// (
+///     vec!
+///     [
+///         load_config::Source::File {file: load_config::SourceFile::Toml("./test/overrides/config.toml".to_string()), merge: false},
+///         load_config::Source::Env {merge: true}, // beats the file
+///         load_config::Source::Overrides(vec![("setting1".to_string(), figment::value::Value::from(false)), ("section.field".to_string(), figment::value::Value::from(3))]), // beats the file and the env var; "section.field" targets the nested setting
+///     ],
+///     None,
+///     None,
+/// ).expect("Loading config failed.");
+///
+/// assert_eq!(config, Config{setting1: false, section: Section{field: 3}}); // the override won, both the flat and the nested, dotted setting
+///
+/// std::env::remove_var("SETTING1");
+/// std::fs::remove_dir_all("./test/overrides").expect("Removing \"./test/overrides\" failed."); // cleanup
+/// ```
+///
+/// # Imports
+/// A `Source::File`'s content may itself list other config files to pull in under `imports_key`, e.g. `imports = ["shared.toml", "/etc/myapp/base.yaml"]`. Each listed path is resolved relative to the importing file's own parent directory, with its format inferred from its extension, and joined in depth-first, with the importing file's own settings taking precedence over its imports. Imports are capped at `IMPORT_RECURSION_LIMIT` levels deep and cyclic imports are rejected, both via a dedicated `Error` variant.
+///
+/// ```
+/// // a nested import: main_import.toml imports base_import.toml, but keeps its own settings
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+///     pub setting2: i32,
+/// }
+///
+/// std::fs::create_dir_all("./test/imports").expect("Creating \"./test/imports\" failed.");
+/// std::fs::write("./test/imports/base_import.toml", "setting1 = true\nsetting2 = 999").expect("Writing base_import.toml failed.");
+/// std::fs::write("./test/imports/main_import.toml", "imports = [\"base_import.toml\"]\nsetting2 = 10").expect("Writing main_import.toml failed.");
+///
+/// let config: Config = load_config::load_config
+/// (
+///     vec![load_config::Source::File {file: load_config::SourceFile::Toml("./test/imports/main_import.toml".to_string()), merge: false}],
+///     None,
+///     None,
+/// ).expect("Loading config failed.");
+///
+/// assert_eq!(config, Config{setting1: true, setting2: 10}); // setting1 pulled in from the import, setting2 kept from the importing file
+///
+/// std::fs::remove_dir_all("./test/imports").expect("Removing \"./test/imports\" failed."); // cleanup
+/// ```
+///
+/// ```
+/// // a cyclic import is rejected rather than recursing forever
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// std::fs::create_dir_all("./test/cyclic_imports").expect("Creating \"./test/cyclic_imports\" failed.");
+/// std::fs::write("./test/cyclic_imports/a.toml", "imports = [\"b.toml\"]\nsetting1 = true").expect("Writing a.toml failed.");
+/// std::fs::write("./test/cyclic_imports/b.toml", "imports = [\"a.toml\"]").expect("Writing b.toml failed.");
+///
+/// let result: Result<Config, load_config::Error> = load_config::load_config
+/// (
+///     vec![load_config::Source::File {file: load_config::SourceFile::Toml("./test/cyclic_imports/a.toml".to_string()), merge: false}],
+///     None,
+///     None,
+/// );
+///
+/// assert!(matches!(result, Err(load_config::Error::ImportCycle {..})));
+///
+/// std::fs::remove_dir_all("./test/cyclic_imports").expect("Removing \"./test/cyclic_imports\" failed."); // cleanup
+/// ```
+///
+/// ```
+/// // a missing import target is reported instead of silently ignored
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// std::fs::create_dir_all("./test/missing_imports").expect("Creating \"./test/missing_imports\" failed.");
+/// std::fs::write("./test/missing_imports/main.toml", "imports = [\"does_not_exist.toml\"]\nsetting1 = true").expect("Writing main.toml failed.");
+///
+/// let result: Result<Config, load_config::Error> = load_config::load_config
+/// (
+///     vec![load_config::Source::File {file: load_config::SourceFile::Toml("./test/missing_imports/main.toml".to_string()), merge: false}],
+///     None,
+///     None,
+/// );
+///
+/// assert!(matches!(result, Err(load_config::Error::MissingImport {..})));
+///
+/// std::fs::remove_dir_all("./test/missing_imports").expect("Removing \"./test/missing_imports\" failed."); // cleanup
+/// ```
+///
 /// # Example
 /// ```
 /// // create test file at test_filepath with TEST_CONTENT to test loading from file
 /// const TEST_CONTENT: &str = "setting1 = true\nsetting2 = 42069";
-/// let test_filepath: &std::path::Path = std::path::Path::new("./test/config.toml");
+/// let test_filepath: &std::path::Path = std::path::Path::new("./test/example/config.toml");
 /// std::fs::create_dir_all(test_filepath.parent().unwrap()).expect(format!("Creating \"{:?}\" failed.", test_filepath.parent().unwrap()).as_str());
 /// std::fs::write(test_filepath, TEST_CONTENT).expect(format!("Writing to \"{test_filepath:?}\" failed.").as_str());
 ///
@@ -60,11 +184,12 @@ use std::io::Write;
 /// (
 ///     vec!
 ///     [
-///         load_config::Source::Env,
-///         load_config::Source::File(load_config::SourceFile::Toml(test_filepath.to_str().unwrap().to_owned())),
-///         load_config::Source::ConfigDefault,
+///         load_config::Source::Env {merge: false},
+///         load_config::Source::File {file: load_config::SourceFile::Toml(test_filepath.to_str().unwrap().to_owned()), merge: false},
+///         load_config::Source::ConfigDefault {merge: false},
 ///     ],
 ///     None,
+///     None,
 /// )
 /// {
 ///     Ok(o) => {config = o;} // loaded config successfully
@@ -76,37 +201,355 @@ use std::io::Write;
 ///
 /// std::fs::remove_dir_all(test_filepath.parent().unwrap()).expect(format!("Removing {test_filepath:?} failed.").as_str()); // cleanup
 /// ```
+///
+/// ```
+/// // env var overrides a TOML value because it is listed after the file and merged, not joined
+/// const TEST_CONTENT: &str = "setting1 = false";
+/// let test_filepath: &std::path::Path = std::path::Path::new("./test/env_overrides_file/config.toml");
+/// std::fs::create_dir_all(test_filepath.parent().unwrap()).expect(format!("Creating \"{:?}\" failed.", test_filepath.parent().unwrap()).as_str());
+/// std::fs::write(test_filepath, TEST_CONTENT).expect(format!("Writing to \"{test_filepath:?}\" failed.").as_str());
+/// std::env::set_var("SETTING1", "true");
+///
+/// #[derive(PartialEq)]
+/// #[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// let config: Config = load_config::load_config
+/// (
+///     vec!
+///     [
+///         load_config::Source::File {file: load_config::SourceFile::Toml(test_filepath.to_str().unwrap().to_owned()), merge: false},
+///         load_config::Source::Env {merge: true}, // listed after the file and merged, so it overrides setting1
+///     ],
+///     None,
+///     None,
+/// ).expect("Loading config failed.");
+///
+/// assert_eq!(config, Config{setting1: true}); // env var won
+///
+/// std::env::remove_var("SETTING1");
+/// std::fs::remove_dir_all(test_filepath.parent().unwrap()).expect(format!("Removing {test_filepath:?} failed.").as_str()); // cleanup
+/// ```
+///
+/// ```
+/// // conversely, a TOML value overrides an env var when the file is listed after the env var and merged
+/// const TEST_CONTENT: &str = "setting1 = false";
+/// let test_filepath: &std::path::Path = std::path::Path::new("./test/file_overrides_env/config.toml");
+/// std::fs::create_dir_all(test_filepath.parent().unwrap()).expect(format!("Creating \"{:?}\" failed.", test_filepath.parent().unwrap()).as_str());
+/// std::fs::write(test_filepath, TEST_CONTENT).expect(format!("Writing to \"{test_filepath:?}\" failed.").as_str());
+/// std::env::set_var("SETTING1", "true");
+///
+/// #[derive(PartialEq)]
+/// #[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// let config: Config = load_config::load_config
+/// (
+///     vec!
+///     [
+///         load_config::Source::Env {merge: false},
+///         load_config::Source::File {file: load_config::SourceFile::Toml(test_filepath.to_str().unwrap().to_owned()), merge: true}, // listed after env and merged, so it overrides setting1
+///     ],
+///     None,
+///     None,
+/// ).expect("Loading config failed.");
+///
+/// assert_eq!(config, Config{setting1: false}); // file won
+///
+/// std::env::remove_var("SETTING1");
+/// std::fs::remove_dir_all(test_filepath.parent().unwrap()).expect(format!("Removing {test_filepath:?} failed.").as_str()); // cleanup
+/// ```
 #[allow(unused_variables)]
-pub fn load_config<'a, T>(sources: Vec<Source>, config_file_default: Option<SourceFile>) -> Result<T, Error>
+pub fn load_config<'a, T>(sources: Vec<Source>, config_file_default: Option<SourceFile>, imports_key: Option<&str>) -> Result<T, Error>
+where
+    T: std::fmt::Debug + Default + serde::Deserialize<'a> + serde::Serialize,
+{
+    let fig: figment::Figment;
+
+
+    (fig, _) = build_figment::<T>(&sources, imports_key.unwrap_or("imports"))?;
+    return extract_or_create_default::<T>(&fig, config_file_default);
+}
+
+
+/// # Summary
+/// Like `load_config`, but additionally attributes every resolved config key to the `Source` that supplied its winning value, and lists any lower-priority sources that also defined the same key but were overridden. Modelled after jj's `AnnotatedValue`, this gives callers a `--show-config-origin`-style capability for debugging where a setting ultimately came from.
+///
+/// # Arguments
+/// - `T`: type of config to load, tries to populate its fields from sources
+/// - `sources`: sources to load config from, prefers earlier sources
+/// - `config_file_default`: default config file to create if a setting is unset, optional
+/// - `imports_key`: key under which a `Source::File`'s own content may list other config files to import, defaults to `"imports"` if `None`, see `load_config`'s "Imports" section
+///
+/// # Returns
+/// - successfully loaded config of type `T` together with one `AnnotatedValue` per key per source that defined it, or an error
+///
+/// # Example
+/// ```
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// std::fs::create_dir_all("./test/annotated").expect("Creating \"./test/annotated\" failed.");
+/// std::fs::write("./test/annotated/config.toml", "setting1 = false").expect("Writing config.toml failed.");
+/// std::env::set_var("SETTING1", "true");
+///
+/// let (config, annotated): (Config, Vec<load_config::AnnotatedValue>) = load_config::load_config_annotated
+/// (
+///     vec!
+///     [
+///         load_config::Source::File {file: load_config::SourceFile::Toml("./test/annotated/config.toml".to_string()), merge: false},
+///         load_config::Source::Env {merge: true}, // listed after the file and merged, so it overrides setting1
+///     ],
+///     None,
+///     None,
+/// ).expect("Loading config failed.");
+///
+/// assert_eq!(config, Config{setting1: true}); // env var won
+/// assert_eq!(annotated.iter().filter(|a| a.path == vec!["setting1".to_string()]).count(), 2); // both the file's and the env var's setting1 are attributed, every other env var gets its own entry too
+/// assert!(annotated.iter().any(|a| a.path == vec!["setting1".to_string()] && matches!(a.source, load_config::Source::Env {..}) && !a.is_overridden)); // env var's value won
+/// assert!(annotated.iter().any(|a| a.path == vec!["setting1".to_string()] && matches!(a.source, load_config::Source::File {..}) && a.is_overridden)); // file's value got overridden
+///
+/// std::env::remove_var("SETTING1");
+/// std::fs::remove_dir_all("./test/annotated").expect("Removing \"./test/annotated\" failed."); // cleanup
+/// ```
+pub fn load_config_annotated<'a, T>(sources: Vec<Source>, config_file_default: Option<SourceFile>, imports_key: Option<&str>) -> Result<(T, Vec<AnnotatedValue>), Error>
 where
     T: std::fmt::Debug + Default + serde::Deserialize<'a> + serde::Serialize,
 {
     let config: T;
+    let fig: figment::Figment;
+    let per_source: Vec<(Source, figment::Figment)>;
+    let mut annotated: Vec<AnnotatedValue> = Vec::new();
+
+
+    (fig, per_source) = build_figment::<T>(&sources, imports_key.unwrap_or("imports"))?;
+    config = extract_or_create_default::<T>(&fig, config_file_default)?;
+
+    for (source, solo_fig) in &per_source // attribute every key this source's own data defines
+    {
+        for (path, value) in leaves(solo_fig)?
+        {
+            let is_overridden: bool = fig.find_value(&path.join("."))?.tag() != value.tag(); // did a higher-priority source win this key instead of this source?
+
+            annotated.push(AnnotatedValue {path, value, source: source.clone(), is_overridden});
+        }
+    }
+
+    return Ok((config, annotated));
+}
+
+
+/// # Summary
+/// Checks `sources` for ambiguous file sources: several `Source::File`s that exist on disk at the same logical location (same directory and filename, differing only by extension), e.g. both `config.toml` and `config.yaml` next to each other. Meant as an opt-in pre-check before `load_config`, since such a stale, same-named file in a second format would otherwise silently shadow or supplement the intended one without any indication of why.
+///
+/// # Arguments
+/// - `sources`: sources to check, only `Source::File`s that exist on disk are considered
+///
+/// # Returns
+/// - `Ok(())` if at most one file per logical location exists, `Err(Error::AmbiguousSource)` naming the first two otherwise
+///
+/// ```
+/// let sources: Vec<load_config::Source> = vec!
+/// [
+///     load_config::Source::File {file: load_config::SourceFile::Toml("./test/ambiguous/config.toml".to_string()), merge: false},
+///     load_config::Source::File {file: load_config::SourceFile::Yaml("./test/ambiguous/config.yaml".to_string()), merge: false},
+/// ];
+///
+/// assert!(load_config::check_ambiguous_sources(&sources).is_ok()); // neither file exists yet: ok
+///
+/// std::fs::create_dir_all("./test/ambiguous").expect("Creating \"./test/ambiguous\" failed.");
+/// std::fs::write("./test/ambiguous/config.toml", "setting1 = true").expect("Writing config.toml failed.");
+///
+/// assert!(load_config::check_ambiguous_sources(&sources).is_ok()); // only one of the two exists: still ok
+///
+/// std::fs::write("./test/ambiguous/config.yaml", "setting1: true").expect("Writing config.yaml failed.");
+///
+/// assert!(matches!(load_config::check_ambiguous_sources(&sources), Err(load_config::Error::AmbiguousSource {..}))); // both exist now: ambiguous
+///
+/// let duplicate_sources: Vec<load_config::Source> = vec!
+/// [
+///     load_config::Source::File {file: load_config::SourceFile::Toml("./test/ambiguous/config.toml".to_string()), merge: false},
+///     load_config::Source::File {file: load_config::SourceFile::Toml("./test/ambiguous/config.toml".to_string()), merge: true},
+/// ];
+///
+/// assert!(load_config::check_ambiguous_sources(&duplicate_sources).is_ok()); // the exact same file listed twice isn't ambiguous, there's only one file on disk
+///
+/// std::fs::remove_dir_all("./test/ambiguous").expect("Removing \"./test/ambiguous\" failed."); // cleanup
+/// ```
+#[cfg(feature = "config_file")]
+pub fn check_ambiguous_sources(sources: &[Source]) -> Result<(), Error>
+{
+    let mut seen: std::collections::HashMap<std::path::PathBuf, String> = std::collections::HashMap::new(); // logical location (filepath without extension) -> first filepath seen there
+
+
+    for source in sources
+    {
+        if let Source::File {file, ..} = source
+        {
+            let filepath: &str = file.filepath();
+
+            if std::path::Path::new(filepath).exists() // only files actually on disk can shadow each other
+            {
+                let location: std::path::PathBuf = std::path::Path::new(filepath).with_extension("");
+
+                if let Some(first) = seen.get(&location)
+                {
+                    if first != filepath // the exact same file listed twice isn't ambiguous, there's only one file on disk
+                    {
+                        return Err(Error::AmbiguousSource {first: first.clone(), second: filepath.to_owned()});
+                    }
+                    continue;
+                }
+                seen.insert(location, filepath.to_owned());
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+
+/// # Summary
+/// Builds the `sources` into one combined `Figment`, preferring earlier sources unless a source is merged, see `Source`'s `merge` field. Also returns each source paired with its own standalone `Figment` (i.e. without the other sources joined in), used by `load_config_annotated` to attribute keys back to the `Source` that defined them.
+///
+/// # Arguments
+/// - `T`: type of config to load, tries to populate its fields from sources
+/// - `sources`: sources to load config from, prefers earlier sources
+/// - `imports_key`: key under which a `Source::File`'s own content may list other config files to import
+///
+/// # Returns
+/// - combined `Figment` with all `sources` joined or merged in, paired with each source's own standalone `Figment`, or an error
+fn build_figment<'a, T>(sources: &[Source], imports_key: &str) -> Result<(figment::Figment, Vec<(Source, figment::Figment)>), Error>
+where
+    T: std::fmt::Debug + Default + serde::Deserialize<'a> + serde::Serialize,
+{
     let mut fig: figment::Figment = figment::Figment::new();
+    let mut override_figs: Vec<figment::Figment> = Vec::new(); // applied last, regardless of where Source::Overrides sits in `sources`
+    let mut per_source: Vec<(Source, figment::Figment)> = Vec::new();
 
 
-    for source in sources // load all sources, prefer earlier sources
+    for source in sources // load all sources, prefer earlier sources unless a source is merged, see `Source`'s `merge` field
     {
+        let solo_fig: figment::Figment = source_figment::<T>(source, imports_key)?; // this source's own data, uninfluenced by any other source
+
         match source
         {
-            Source::ConfigDefault => fig = fig.join(figment::providers::Serialized::defaults(T::default())),
-            Source::Env => fig = fig.join(figment::providers::Env::raw().lowercase(false)), // don't lowercase env variable names, keep unchanged
-            #[cfg(feature = "config_file")]
-            Source::File(source_file) => match source_file
+            Source::Overrides(_) => override_figs.push(solo_fig.clone()),
+            _ => fig = join_or_merge(fig, solo_fig.clone(), source.merge()),
+        }
+        per_source.push((source.clone(), solo_fig));
+    }
+
+    for override_fig in override_figs // merged after every other source, so overrides always win, see `Source`'s documentation
+    {
+        fig = fig.merge(override_fig);
+    }
+
+    return Ok((fig, per_source));
+}
+
+
+/// # Summary
+/// Loads `source`'s own data into a standalone `Figment`, not influenced by any other source.
+///
+/// # Arguments
+/// - `T`: type of config to load, tries to populate its fields from sources
+/// - `source`: source to load
+/// - `imports_key`: key under which a `Source::File`'s own content may list other config files to import
+///
+/// # Returns
+/// - `source`'s own data as a `Figment`, or an error
+fn source_figment<'a, T>(source: &Source, imports_key: &str) -> Result<figment::Figment, Error>
+where
+    T: std::fmt::Debug + Default + serde::Deserialize<'a> + serde::Serialize,
+{
+    return Ok(match source
+    {
+        Source::ConfigDefault {..} => figment::Figment::new().join(figment::providers::Serialized::defaults(T::default())),
+        Source::Env {..} => figment::Figment::new().join(figment::providers::Env::raw().lowercase(false)), // don't lowercase env variable names, keep unchanged
+        #[cfg(feature = "config_file")]
+        Source::File {file, ..} => load_file_with_imports(file, imports_key, 0, &[])?, // resolves file's own imports, file's own settings take precedence over them
+        Source::Overrides(overrides) => figment::Figment::new().join(figment::providers::Serialized::defaults(dotted_dict(overrides))),
+    });
+}
+
+
+/// # Summary
+/// Builds a (possibly nested) `figment::value::Dict` out of `overrides`, splitting each key on `.` so a dotted key like `"section.field"` targets a nested setting the same way a struct field path would.
+///
+/// # Arguments
+/// - `overrides`: key/value pairs to turn into a `Dict`, keys may be dotted
+///
+/// # Returns
+/// - `Dict` with `overrides` inserted, nested according to their dotted keys
+fn dotted_dict(overrides: &[(String, figment::value::Value)]) -> figment::value::Dict
+{
+    let mut dict: figment::value::Dict = figment::value::Dict::new();
+
+    for (key, value) in overrides
+    {
+        insert_dotted(&mut dict, &mut key.split('.'), value.clone());
+    }
+
+    return dict;
+}
+
+
+/// # Summary
+/// Inserts `value` into `dict` at the path described by `keys`, creating nested `Dict`s for every key segment but the last.
+///
+/// # Arguments
+/// - `dict`: dict to insert into
+/// - `keys`: remaining dotted key segments, consumed as nesting proceeds
+/// - `value`: value to insert at the full key path
+fn insert_dotted(dict: &mut figment::value::Dict, keys: &mut std::str::Split<'_, char>, value: figment::value::Value)
+{
+    if let Some(key) = keys.next()
+    {
+        if keys.clone().next().is_some() // more segments remain: nest further
+        {
+            let entry: &mut figment::value::Value = dict.entry(key.to_owned()).or_insert_with(|| figment::value::Value::from(figment::value::Dict::new()));
+
+            if let figment::value::Value::Dict(_, inner) = entry
             {
-                #[cfg(feature = "json_file")]
-                SourceFile::Json(filepath) => fig = fig.join(figment::providers::Json::file(filepath)),
-                #[cfg(feature = "toml_file")]
-                SourceFile::Toml(filepath) => fig = fig.join(figment::providers::Toml::file(filepath)),
-                #[cfg(feature = "yaml_file")]
-                SourceFile::Yaml(filepath) => fig = fig.join(figment::providers::Yaml::file(filepath)),
-            },
-        };
+                insert_dotted(inner, keys, value);
+            }
+        }
+        else // last segment: insert the value itself
+        {
+            dict.insert(key.to_owned(), value);
+        }
     }
+}
 
-    match fig.extract() // Figment -> T
+
+/// # Summary
+/// Extracts `T` from `fig`. If extraction fails because a setting is unset, `config_file_default` is `Some`, and the specified filepath does not exist yet, offers to create a default config file there instead.
+///
+/// # Arguments
+/// - `T`: type of config to extract, tries to populate its fields from `fig`
+/// - `fig`: figment to extract `T` from
+/// - `config_file_default`: default config file to create if a setting is unset, optional
+///
+/// # Returns
+/// - successfully extracted config of type `T` or an error
+#[allow(unused_variables)]
+fn extract_or_create_default<'a, T>(fig: &figment::Figment, config_file_default: Option<SourceFile>) -> Result<T, Error>
+where
+    T: std::fmt::Debug + Default + serde::Deserialize<'a> + serde::Serialize,
+{
+    return match fig.extract() // Figment -> T
     {
-        Ok(c) => config = c, // loaded config successfully
+        Ok(c) => Ok(c), // loaded config successfully
 
         Err(e) => // loading config failed
         {
@@ -115,15 +558,7 @@ where
             {
                 if let Some(s) = config_file_default // and default config file specified
                 {
-                    let filepath: String = match s.clone() // extract filepath where default config should be created
-                    {
-                        #[cfg(feature = "json_file")]
-                        SourceFile::Json(filepath) => filepath,
-                        #[cfg(feature = "toml_file")]
-                        SourceFile::Toml(filepath) => filepath,
-                        #[cfg(feature = "yaml_file")]
-                        SourceFile::Yaml(filepath) => filepath,
-                    };
+                    let filepath: String = s.filepath().to_owned(); // filepath where default config should be created
                     if !std::path::Path::new(&filepath).exists() // and if file does not already exist, don't want to overwrite existing but faulty config file, rather give missing field error to user
                     {
                         create_default_file::<T>(&s)?; // create default config file, upon failure propagate this error over the missing field error
@@ -131,11 +566,133 @@ where
                     }
                 }
             }
-            return Err(e.into()); // if not because of missing field: just forward figment error
+            Err(e.into()) // if not because of missing field: just forward figment error
         }
+    };
+}
+
+
+/// # Summary
+/// Joins or merges `provider` into `fig` depending on `merge`. Joining only fills settings not yet set by an earlier source, merging overrides a setting already set by an earlier source.
+///
+/// # Arguments
+/// - `fig`: figment to join or merge `provider` into
+/// - `provider`: provider to join or merge into `fig`
+/// - `merge`: if `true`, merge `provider` into `fig`, overriding already set settings; if `false`, join `provider` into `fig`, only filling unset settings
+///
+/// # Returns
+/// - `fig` with `provider` joined or merged in
+fn join_or_merge(fig: figment::Figment, provider: impl figment::Provider, merge: bool) -> figment::Figment
+{
+    return if merge {fig.merge(provider)} else {fig.join(provider)};
+}
+
+
+/// # Summary
+/// Recursively walks `fig`'s merged data for its default profile and collects every leaf (i.e. non-`Dict`) value together with its fully-qualified, dot-separated key path.
+///
+/// # Arguments
+/// - `fig`: figment to walk
+///
+/// # Returns
+/// - every leaf value in `fig` paired with its key path, or an error
+fn leaves(fig: &figment::Figment) -> Result<Vec<(Vec<String>, figment::value::Value)>, Error>
+{
+    let data: figment::value::Map<figment::Profile, figment::value::Dict> = fig.data()?;
+    let mut out: Vec<(Vec<String>, figment::value::Value)> = Vec::new();
+
+
+    if let Some(dict) = data.get(fig.profile())
+    {
+        walk_dict(&mut Vec::new(), dict, &mut out);
     }
 
-    return Ok(config);
+    return Ok(out);
+}
+
+
+/// # Summary
+/// Recursively walks `dict`, appending every leaf (i.e. non-`Dict`) value together with its key path (`path` plus the keys walked so far) to `out`.
+///
+/// # Arguments
+/// - `path`: key path walked so far, mutated in place and restored before returning
+/// - `dict`: dict to walk
+/// - `out`: leaf values found so far, appended to
+fn walk_dict(path: &mut Vec<String>, dict: &figment::value::Dict, out: &mut Vec<(Vec<String>, figment::value::Value)>)
+{
+    for (key, value) in dict
+    {
+        path.push(key.clone());
+        match value
+        {
+            figment::value::Value::Dict(_, inner) => walk_dict(path, inner, out),
+            _ => out.push((path.clone(), value.clone())),
+        };
+        path.pop();
+    }
+}
+
+
+/// # Summary
+/// Loads `source_file` into a `Figment`, then recursively resolves and joins in any config files listed under `imports_key` inside it, with `source_file`'s own settings taking precedence over its imports. Imports are resolved relative to `source_file`'s parent directory, their format inferred from their file extension, and processed depth-first.
+///
+/// A directly configured `Source::File` (`depth == 0`) is allowed not to exist yet, the same way it always could: it is handed to figment as-is, so a later `Kind::MissingField` can still trigger `config_file_default`. Only files reached via an actual import (`depth > 0`) are required to exist upfront and are recorded for the cycle check; the directly configured file is not recorded, so an import chain leading back to it is reported one level later, when the file it imports is reached a second time.
+///
+/// # Arguments
+/// - `source_file`: file (and format) to load
+/// - `imports_key`: key under which a list of import paths is looked up inside `source_file`
+/// - `depth`: current import recursion depth, `0` for a directly configured `Source::File`
+/// - `ancestors`: canonicalised paths of the imported files above `source_file` in this import chain, used to detect cycles; does not include sibling imports or the directly configured `Source::File` at the top of the chain, only this branch's own imported ancestors
+///
+/// # Returns
+/// - `Figment` with `source_file` and all of its (transitive) imports joined in, or an error
+#[cfg(feature = "config_file")]
+fn load_file_with_imports(source_file: &SourceFile, imports_key: &str, depth: usize, ancestors: &[std::path::PathBuf]) -> Result<figment::Figment, Error>
+{
+    let mut ancestors: Vec<std::path::PathBuf> = ancestors.to_vec(); // own copy for this branch, so sibling imports don't see each other
+    let file_dir: std::path::PathBuf;
+    let mut fig: figment::Figment;
+    let imports: Vec<String>;
+
+
+    if depth > IMPORT_RECURSION_LIMIT // guard against runaway/unbounded import chains
+    {
+        return Err(Error::ImportRecursionLimitExceeded {filepath: source_file.filepath().to_owned()});
+    }
+    if depth > 0 // only imported files must exist upfront and be checked for cycles, a directly configured Source::File may still be missing, see above
+    {
+        let canonical_path: std::path::PathBuf = std::fs::canonicalize(source_file.filepath()).map_err(|e| Error::MissingImport {filepath: source_file.filepath().to_owned(), source: e})?;
+
+        if ancestors.contains(&canonical_path) // already an ancestor of source_file in this import chain
+        {
+            return Err(Error::ImportCycle {filepath: source_file.filepath().to_owned()});
+        }
+        ancestors.push(canonical_path);
+    }
+    file_dir = std::path::Path::new(source_file.filepath()).parent().unwrap_or(std::path::Path::new("")).to_path_buf();
+
+    fig = match source_file
+    {
+        #[cfg(feature = "json_file")]
+        SourceFile::Json(filepath) => figment::Figment::new().join(figment::providers::Json::file(filepath)),
+        #[cfg(feature = "toml_file")]
+        SourceFile::Toml(filepath) => figment::Figment::new().join(figment::providers::Toml::file(filepath)),
+        #[cfg(feature = "yaml_file")]
+        SourceFile::Yaml(filepath) => figment::Figment::new().join(figment::providers::Yaml::file(filepath)),
+        #[cfg(feature = "ron_file")]
+        SourceFile::Ron(filepath) => figment::Figment::new().join(Ron::file(filepath)),
+    };
+    imports = fig.extract_inner::<Vec<String>>(imports_key).unwrap_or_default(); // no imports key present or wrong type: treat as having no imports
+
+    for import in imports // join imports after the file itself, so the file's own settings take precedence over them
+    {
+        let import_path: std::path::PathBuf = file_dir.join(import);
+        let import_source_file: SourceFile = SourceFile::from_path(&import_path).ok_or_else(|| Error::UnsupportedImportFormat {filepath: import_path.to_string_lossy().into_owned()})?;
+
+        fig = fig.join(load_file_with_imports(&import_source_file, imports_key, depth + 1, &ancestors)?);
+    }
+
+    return Ok(fig);
 }
 
 
@@ -152,18 +709,7 @@ where
 {
     let mut file: std::fs::File; // file to write to
     let file_content: String; // config serialised to write to file
-    let filepath: &str; // path to file to be created
-
-
-    filepath = match config_file_default // extract filepath
-    {
-        #[cfg(feature = "json_file")]
-        SourceFile::Json(filepath) => filepath,
-        #[cfg(feature = "toml_file")]
-        SourceFile::Toml(filepath) => filepath,
-        #[cfg(feature = "yaml_file")]
-        SourceFile::Yaml(filepath) => filepath,
-    };
+    let filepath: &str = config_file_default.filepath(); // path to file to be created
 
 
     file_content = match config_file_default
@@ -174,6 +720,8 @@ where
         SourceFile::Toml(_) => toml::to_string_pretty(&T::default())?, // serialise config to toml
         #[cfg(feature = "yaml_file")]
         SourceFile::Yaml(_) => serde_yaml::to_string(&T::default())?, // serialise config to yaml
+        #[cfg(feature = "ron_file")]
+        SourceFile::Ron(_) => ron::ser::to_string_pretty(&T::default(), ron::ser::PrettyConfig::default())?, // serialise config to RON
     };
 
     if let Err(e) = std::fs::create_dir_all(std::path::Path::new(filepath).parent().unwrap_or(std::path::Path::new(""))) // create all parent directories
@@ -195,14 +743,49 @@ where
 
 
 /// # Summary
-/// Config source. Either environment variables, a file or config default.
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Config source. Either environment variables, a file, config default, or runtime overrides. `merge` controls this source's precedence against sources already loaded: `false` joins it, only filling settings not yet set by an earlier source; `true` merges it, overriding a setting already set by an earlier source. See `load_config`'s "Merge Semantics" section. `Overrides` always merges, mirroring a `--set key=value`-style CLI flag that is meant to beat every other source regardless of where it sits in `sources`.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Source // could not use list of trait objects (Vec<Box<dyn figment::Provider>>) because figment::merge() requires a type known at compile time
 {
-    ConfigDefault,
-    Env,
+    ConfigDefault {merge: bool},
+    Env {merge: bool},
     #[cfg(feature = "config_file")]
-    File(SourceFile),
+    File {file: SourceFile, merge: bool},
+    /// runtime key/value overrides, e.g. parsed from `--set section.field=value` CLI flags, at the highest precedence. Keys may be dotted (`"section.field"`) to target a nested setting.
+    Overrides(Vec<(String, figment::value::Value)>),
+}
+
+
+impl Source
+{
+    /// # Summary
+    /// Returns this `Source`'s `merge` flag, regardless of its variant. `Overrides` always merges, see `Source`'s documentation.
+    ///
+    /// # Returns
+    /// - this `Source`'s `merge` flag
+    fn merge(&self) -> bool
+    {
+        return match self
+        {
+            Source::ConfigDefault {merge} => *merge,
+            Source::Env {merge} => *merge,
+            #[cfg(feature = "config_file")]
+            Source::File {merge, ..} => *merge,
+            Source::Overrides(_) => true,
+        };
+    }
+}
+
+
+/// # Summary
+/// A config value as resolved by `load_config_annotated`: the fully-qualified, dot-separated key path it was found at, the value itself, the `Source` it came from, and whether a higher-priority source defined the same key and thus overrode it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedValue
+{
+    pub path: Vec<String>,
+    pub value: figment::value::Value,
+    pub source: Source,
+    pub is_overridden: bool,
 }
 
 
@@ -217,4 +800,187 @@ pub enum SourceFile
     Toml(String),
     #[cfg(feature = "yaml_file")]
     Yaml(String),
+    #[cfg(feature = "ron_file")]
+    Ron(String),
+}
+
+
+/// # Summary
+/// `figment::providers::Format` for RON, letting `Ron::file(filepath)` build a `figment::Provider` the same way `figment::providers::Json`/`Toml`/`Yaml` do, since figment does not ship a RON format itself.
+///
+/// ```
+/// // round-trip: create a default RON config file, then load it back
+/// #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize, Default)]
+/// struct Config
+/// {
+///     pub setting1: bool,
+/// }
+///
+/// std::fs::create_dir_all("./test/ron").expect("Creating \"./test/ron\" failed.");
+/// let filepath: &str = "./test/ron/config.ron";
+/// match load_config::load_config::<Config>(vec![load_config::Source::File {file: load_config::SourceFile::Ron(filepath.to_string()), merge: false}], Some(load_config::SourceFile::Ron(filepath.to_string())), None)
+/// {
+///     Err(load_config::Error::CreatedDefaultFile {..}) => {} // default RON file created since setting1 was unset
+///     _ => panic!("Expected a default RON config file to be created."),
+/// }
+///
+/// let config: Config = load_config::load_config(vec![load_config::Source::File {file: load_config::SourceFile::Ron(filepath.to_string()), merge: false}], None, None).expect("Loading config failed.");
+/// assert_eq!(config, Config::default());
+///
+/// std::fs::remove_dir_all("./test/ron").expect("Removing \"./test/ron\" failed."); // cleanup
+/// ```
+#[cfg(feature = "ron_file")]
+struct Ron;
+
+
+#[cfg(feature = "ron_file")]
+impl figment::providers::Format for Ron
+{
+    type Error = ron::Error;
+
+    const NAME: &'static str = "RON";
+
+    fn from_str<'de, T: serde::Deserialize<'de>>(string: &str) -> Result<T, Self::Error>
+    {
+        return ron::de::from_str(string);
+    }
+}
+
+
+#[cfg(feature = "config_file")]
+impl SourceFile
+{
+    /// # Summary
+    /// Returns the filepath contained in this `SourceFile`, regardless of its format.
+    ///
+    /// # Returns
+    /// - filepath contained in this `SourceFile`
+    pub fn filepath(&self) -> &str
+    {
+        return match self
+        {
+            #[cfg(feature = "json_file")]
+            SourceFile::Json(filepath) => filepath,
+            #[cfg(feature = "toml_file")]
+            SourceFile::Toml(filepath) => filepath,
+            #[cfg(feature = "yaml_file")]
+            SourceFile::Yaml(filepath) => filepath,
+            #[cfg(feature = "ron_file")]
+            SourceFile::Ron(filepath) => filepath,
+        };
+    }
+
+
+    /// # Summary
+    /// Infers a `SourceFile`'s format from `path`'s file extension (`json`, `toml`, `yaml`/`yml`, `ron`) and wraps `path` in it. Used to resolve imports, whose format isn't stated explicitly.
+    ///
+    /// # Arguments
+    /// - `path`: path whose extension determines the inferred format
+    ///
+    /// # Returns
+    /// - `Some(SourceFile)` if `path`'s extension is a supported, enabled format, `None` otherwise
+    pub fn from_path(path: &std::path::Path) -> Option<SourceFile>
+    {
+        return match path.extension().and_then(|e| e.to_str())
+        {
+            #[cfg(feature = "json_file")]
+            Some("json") => Some(SourceFile::Json(path.to_string_lossy().into_owned())),
+            #[cfg(feature = "toml_file")]
+            Some("toml") => Some(SourceFile::Toml(path.to_string_lossy().into_owned())),
+            #[cfg(feature = "yaml_file")]
+            Some("yaml" | "yml") => Some(SourceFile::Yaml(path.to_string_lossy().into_owned())),
+            #[cfg(feature = "ron_file")]
+            Some("ron") => Some(SourceFile::Ron(path.to_string_lossy().into_owned())),
+            _ => None,
+        };
+    }
+
+
+    /// # Summary
+    /// Builds a `SourceFile::Toml` pointing at `file_name` inside `app_name`'s OS-appropriate per-user config directory (e.g. `$XDG_CONFIG_HOME`/`~/.config` on Linux, `~/Library/Application Support` on macOS, `%APPDATA%` on Windows), so callers don't have to hardcode or reimplement that resolution themselves. Usable both as a load source and as `config_file_default`.
+    ///
+    /// # Arguments
+    /// - `app_name`: name of the application, used to determine its per-user config directory
+    /// - `file_name`: name of the config file inside that directory
+    ///
+    /// # Returns
+    /// - `SourceFile::Toml` at `file_name` inside `app_name`'s per-user config directory, or an error if that directory cannot be determined, e.g. because no valid home directory was found for the current user
+    ///
+    /// ```
+    /// std::env::set_var("XDG_CONFIG_HOME", "/tmp/load_config_test_xdg");
+    /// let source_file: load_config::SourceFile = load_config::SourceFile::toml_in_config_dir("myapp", "config.toml").unwrap();
+    /// assert_eq!(source_file, load_config::SourceFile::Toml("/tmp/load_config_test_xdg/myapp/config.toml".to_string()));
+    /// std::env::remove_var("XDG_CONFIG_HOME");
+    /// ```
+    #[cfg(all(feature = "config_dir", feature = "toml_file"))]
+    pub fn toml_in_config_dir(app_name: &str, file_name: &str) -> Result<SourceFile, Error>
+    {
+        return Ok(SourceFile::Toml(config_dir_filepath(app_name, file_name)?));
+    }
+
+
+    /// # Summary
+    /// Builds a `SourceFile::Json` pointing at `file_name` inside `app_name`'s OS-appropriate per-user config directory. See `toml_in_config_dir` for details.
+    ///
+    /// # Arguments
+    /// - `app_name`: name of the application, used to determine its per-user config directory
+    /// - `file_name`: name of the config file inside that directory
+    ///
+    /// # Returns
+    /// - `SourceFile::Json` at `file_name` inside `app_name`'s per-user config directory, or an error if that directory cannot be determined, e.g. because no valid home directory was found for the current user
+    #[cfg(all(feature = "config_dir", feature = "json_file"))]
+    pub fn json_in_config_dir(app_name: &str, file_name: &str) -> Result<SourceFile, Error>
+    {
+        return Ok(SourceFile::Json(config_dir_filepath(app_name, file_name)?));
+    }
+
+
+    /// # Summary
+    /// Builds a `SourceFile::Yaml` pointing at `file_name` inside `app_name`'s OS-appropriate per-user config directory. See `toml_in_config_dir` for details.
+    ///
+    /// # Arguments
+    /// - `app_name`: name of the application, used to determine its per-user config directory
+    /// - `file_name`: name of the config file inside that directory
+    ///
+    /// # Returns
+    /// - `SourceFile::Yaml` at `file_name` inside `app_name`'s per-user config directory, or an error if that directory cannot be determined, e.g. because no valid home directory was found for the current user
+    #[cfg(all(feature = "config_dir", feature = "yaml_file"))]
+    pub fn yaml_in_config_dir(app_name: &str, file_name: &str) -> Result<SourceFile, Error>
+    {
+        return Ok(SourceFile::Yaml(config_dir_filepath(app_name, file_name)?));
+    }
+
+
+    /// # Summary
+    /// Builds a `SourceFile::Ron` pointing at `file_name` inside `app_name`'s OS-appropriate per-user config directory. See `toml_in_config_dir` for details.
+    ///
+    /// # Arguments
+    /// - `app_name`: name of the application, used to determine its per-user config directory
+    /// - `file_name`: name of the config file inside that directory
+    ///
+    /// # Returns
+    /// - `SourceFile::Ron` at `file_name` inside `app_name`'s per-user config directory, or an error if that directory cannot be determined, e.g. because no valid home directory was found for the current user
+    #[cfg(all(feature = "config_dir", feature = "ron_file"))]
+    pub fn ron_in_config_dir(app_name: &str, file_name: &str) -> Result<SourceFile, Error>
+    {
+        return Ok(SourceFile::Ron(config_dir_filepath(app_name, file_name)?));
+    }
+}
+
+
+/// # Summary
+/// Resolves `app_name`'s OS-appropriate per-user config directory and joins `file_name` onto it.
+///
+/// # Arguments
+/// - `app_name`: name of the application, used to determine its per-user config directory
+/// - `file_name`: name of the config file inside that directory
+///
+/// # Returns
+/// - `file_name` inside `app_name`'s per-user config directory, or an error if that directory cannot be determined, e.g. because no valid home directory was found for the current user
+#[cfg(feature = "config_dir")]
+fn config_dir_filepath(app_name: &str, file_name: &str) -> Result<String, Error>
+{
+    let project_dirs: directories::ProjectDirs = directories::ProjectDirs::from("", "", app_name).ok_or_else(|| Error::ConfigDirNotFound {app_name: app_name.to_owned()})?;
+
+    return Ok(project_dirs.config_dir().join(file_name).to_string_lossy().into_owned());
 }